@@ -1,99 +1,523 @@
 use tauri_plugin_shell::ShellExt;
-use tauri_plugin_shell::process::CommandEvent;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 use std::env;
-use tauri::Manager;
+use std::net::TcpListener;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 use tauri::menu::{Menu, Submenu, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri_plugin_updater::UpdaterExt;
+use tauri_plugin_dialog::DialogExt;
+use rust_i18n::t;
+
+// メニュー/トレイ/ダイアログの文言は locales/ 配下の YAML カタログから解決する。
+// 未定義キーは英語 (en) にフォールバックする。
+rust_i18n::i18n!("locales", fallback = "en");
+
+/// サイドカー（djaly-server）の稼働状態を保持する共有ステート。
+/// 動的に割り当てたポートをフロントエンドと共有するために使う。
+#[derive(Default)]
+struct ServerState {
+    port: Mutex<u16>,
+}
+
+/// 稼働中のサイドカー子プロセスのハンドル。
+/// アプリ終了時に確実に kill できるよう managed state として保持する。
+#[derive(Default)]
+struct ChildState(Mutex<Option<CommandChild>>);
+
+/// アプリが終了処理に入ったことを示すフラグ。
+/// 終了中にサイドカーが再起動されないよう、スーパーバイザがループ内で参照する。
+#[derive(Default)]
+struct ShutdownFlag(AtomicBool);
+
+/// 保持中のサイドカー子プロセスを停止する。
+/// Windows では子孫プロセス（Python インタプリタ）ごと確実に終了させる。
+///
+/// `child.kill()` はシグナルを送るだけで即座に返るため、終了を待つ保証はない。
+/// 次回起動時のポート衝突を避けるべく、ベストエフォートでポートが解放されるまで
+/// 短時間だけブロックしてから返る。
+fn kill_sidecar<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(child) = app.state::<ChildState>().0.lock().unwrap().take() {
+        #[cfg(windows)]
+        {
+            let _ = std::process::Command::new("taskkill")
+                .args(["/PID", &child.pid().to_string(), "/T", "/F"])
+                .status();
+        }
+        let _ = child.kill();
+
+        // プロセスが実際に終了してポートを手放すまでベストエフォートで待つ。
+        let port = *app.state::<ServerState>().port.lock().unwrap();
+        if port != 0 {
+            let deadline = Instant::now() + Duration::from_secs(2);
+            while Instant::now() < deadline {
+                if TcpListener::bind(("127.0.0.1", port)).is_ok() {
+                    break;
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+}
+
+/// webview に通知するサイドカーの状態遷移。
+#[derive(Clone, serde::Serialize)]
+struct SidecarStatus {
+    /// "spawning" / "healthy" / "crashed" / "stopped" のいずれか。
+    state: String,
+    port: u16,
+}
+
+/// フロントエンドが接続先を知るための現在のサイドカーポートを返す。
+/// まだ割り当て前の場合は 0 を返す。
+#[tauri::command]
+fn get_server_port(state: State<'_, ServerState>) -> u16 {
+    *state.port.lock().unwrap()
+}
+
+/// 確認済みでインストール待ちのアップデートを保持する共有ステート。
+#[derive(Default)]
+struct PendingUpdate(Mutex<Option<tauri_plugin_updater::Update>>);
+
+/// 利用可能なアップデートの情報を webview に渡すためのペイロード。
+#[derive(Clone, serde::Serialize)]
+struct UpdateInfo {
+    version: String,
+    current_version: String,
+    /// リリースノート (存在する場合)。
+    notes: Option<String>,
+}
+
+/// ダウンロード進捗を webview に通知するためのペイロード。
+#[derive(Clone, serde::Serialize)]
+struct DownloadProgress {
+    downloaded: usize,
+    total: Option<u64>,
+}
+
+/// アップデートの有無を問い合わせ、見つかればインストール待ちとして保持する。
+async fn perform_update_check(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let updater = app.updater().map_err(|err| err.to_string())?;
+    match updater.check().await.map_err(|err| err.to_string())? {
+        Some(update) => {
+            let info = UpdateInfo {
+                version: update.version.clone(),
+                current_version: update.current_version.clone(),
+                notes: update.body.clone(),
+            };
+            *app.state::<PendingUpdate>().0.lock().unwrap() = Some(update);
+            Ok(Some(info))
+        }
+        None => {
+            *app.state::<PendingUpdate>().0.lock().unwrap() = None;
+            Ok(None)
+        }
+    }
+}
+
+/// リリースエンドポイントへ問い合わせ、利用可能なアップデート情報を返す。
+#[tauri::command]
+async fn check_for_update(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    perform_update_check(&app).await
+}
+
+/// 保持中のアップデートをダウンロード＆インストールし、完了後に再起動する。
+#[tauri::command]
+async fn install_update(app: AppHandle) -> Result<(), String> {
+    let update = app.state::<PendingUpdate>().0.lock().unwrap().take();
+    let Some(update) = update else {
+        return Err("No update is available to install".to_string());
+    };
+
+    let mut downloaded = 0usize;
+    let progress_app = app.clone();
+    update
+        .download_and_install(
+            move |chunk, total| {
+                downloaded += chunk;
+                let _ = progress_app.emit(
+                    "update-download-progress",
+                    DownloadProgress { downloaded, total },
+                );
+            },
+            || {},
+        )
+        .await
+        .map_err(|err| err.to_string())?;
+
+    // インストール済みバイナリで起動し直す (tauri_plugin_process 経由の再起動と同等)。
+    app.restart()
+}
+
+/// ロケール上書き設定を保存するファイルのパス。
+fn locale_file<R: Runtime>(app: &AppHandle<R>) -> Option<std::path::PathBuf> {
+    app.path().app_config_dir().ok().map(|dir| dir.join("locale"))
+}
+
+/// 永続化されたロケール上書き設定を読み込む。
+fn load_locale_override<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    let path = locale_file(app)?;
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|raw| raw.trim().to_string())
+        .filter(|raw| !raw.is_empty())
+}
+
+/// ロケール上書き設定を設定ディレクトリへ永続化する (tauri_plugin_fs と同じ config_dir)。
+fn persist_locale<R: Runtime>(app: &AppHandle<R>, locale: &str) {
+    if let Some(path) = locale_file(app) {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, locale);
+    }
+}
+
+/// 起動時に使用するロケールを決定する。
+/// 優先順位は「永続化された上書き設定 → OS の UI 言語 → en」。
+fn detect_locale<R: Runtime>(app: &AppHandle<R>) -> String {
+    if let Some(overridden) = load_locale_override(app) {
+        return overridden;
+    }
+    sys_locale::get_locale()
+        .and_then(|locale| locale.split(['-', '_']).next().map(str::to_string))
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// 現在のロケールでネイティブメニューを組み立てる。
+fn build_menu<R: Runtime>(handle: &AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let menu = Menu::new(handle)?;
+
+    #[cfg(target_os = "macos")]
+    {
+        let app_menu = Submenu::new(handle, "Djaly", true)?;
+        app_menu.append(&MenuItem::with_id(handle, "check_for_update", t!("menu.app.check_for_update"), true, None::<&str>)?)?;
+        app_menu.append(&PredefinedMenuItem::separator(handle)?)?;
+        app_menu.append(&PredefinedMenuItem::hide(handle, Some(&t!("menu.app.hide")))?)?;
+        app_menu.append(&PredefinedMenuItem::hide_others(handle, Some(&t!("menu.app.hide_others")))?)?;
+        app_menu.append(&PredefinedMenuItem::quit(handle, Some(&t!("menu.app.quit")))?)?;
+        menu.append(&app_menu)?;
+    }
+
+    let edit_menu = Submenu::new(handle, t!("menu.edit.title"), true)?;
+    edit_menu.append(&PredefinedMenuItem::undo(handle, Some(&t!("menu.edit.undo")))?)?;
+    edit_menu.append(&PredefinedMenuItem::redo(handle, Some(&t!("menu.edit.redo")))?)?;
+    edit_menu.append(&PredefinedMenuItem::separator(handle)?)?;
+    edit_menu.append(&PredefinedMenuItem::cut(handle, Some(&t!("menu.edit.cut")))?)?;
+    edit_menu.append(&PredefinedMenuItem::copy(handle, Some(&t!("menu.edit.copy")))?)?;
+    edit_menu.append(&PredefinedMenuItem::paste(handle, Some(&t!("menu.edit.paste")))?)?;
+    edit_menu.append(&PredefinedMenuItem::select_all(handle, Some(&t!("menu.edit.select_all")))?)?;
+    menu.append(&edit_menu)?;
+
+    let view_menu = Submenu::new(handle, t!("menu.view.title"), true)?;
+    view_menu.append(&PredefinedMenuItem::fullscreen(handle, Some(&t!("menu.view.fullscreen")))?)?;
+    view_menu.append(&MenuItem::with_id(handle, "toggle_devtools", t!("menu.view.toggle_devtools"), true, None::<&str>)?)?;
+    menu.append(&view_menu)?;
+
+    // クロスプラットフォームの Help メニュー (macOS 以外でもアップデート確認を提供する)。
+    let help_menu = Submenu::new(handle, t!("menu.help.title"), true)?;
+    help_menu.append(&MenuItem::with_id(handle, "about", t!("menu.help.about"), true, None::<&str>)?)?;
+    help_menu.append(&MenuItem::with_id(handle, "check_for_update", t!("menu.help.check_for_update"), true, None::<&str>)?)?;
+    menu.append(&help_menu)?;
+
+    Ok(menu)
+}
+
+/// 現在のロケールでトレイアイコンのメニューを組み立てる。
+/// `build_tray`（初期生成）と `set_locale`（再構築）の双方から共用する。
+fn build_tray_menu<R: Runtime>(handle: &AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let tray_menu = Menu::new(handle)?;
+    tray_menu.append(&MenuItem::with_id(handle, "tray_show", t!("tray.show"), true, None::<&str>)?)?;
+    tray_menu.append(&PredefinedMenuItem::separator(handle)?)?;
+    tray_menu.append(&MenuItem::with_id(handle, "quit", t!("tray.quit"), true, None::<&str>)?)?;
+    Ok(tray_menu)
+}
+
+/// 現在のロケールでトレイアイコンのメニューを組み立て、トレイを生成する。
+fn build_tray<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let tray_menu = build_tray_menu(app)?;
+
+    TrayIconBuilder::with_id("main")
+        .menu(&tray_menu)
+        .on_menu_event(|app, event| {
+            if event.id() == "quit" {
+                app.exit(0);
+            } else if event.id() == "tray_show" {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+        })
+        .build(app)?;
+    Ok(())
+}
+
+/// ロケールを切り替え、設定を永続化して、ネイティブメニュー／トレイを再構築する。
+#[tauri::command]
+fn set_locale(app: AppHandle, locale: String) -> Result<(), String> {
+    rust_i18n::set_locale(&locale);
+    persist_locale(&app, &locale);
+
+    let menu = build_menu(&app).map_err(|err| err.to_string())?;
+    app.set_menu(menu).map_err(|err| err.to_string())?;
+
+    if let Some(tray) = app.tray_by_id("main") {
+        let tray_menu = build_tray_menu(&app).map_err(|err| err.to_string())?;
+        tray.set_menu(Some(tray_menu)).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+/// OS に空き TCP ポートを割り当てさせ、その番号を返す。
+/// 番号を読み取った直後にリスナーを閉じるので、子プロセスが同じポートを束縛できる。
+fn find_free_port() -> std::io::Result<u16> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    drop(listener);
+    Ok(port)
+}
+
+/// `GET /health` が 200 を返すまで（またはタイムアウトまで）ポーリングする。
+async fn wait_for_health(port: u16, timeout: Duration) -> bool {
+    let url = format!("http://127.0.0.1:{}/health", port);
+    let client = tauri_plugin_http::reqwest::Client::new();
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if let Ok(resp) = client.get(&url).send().await {
+            if resp.status().is_success() {
+                return true;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    false
+}
+
+/// サイドカーを一度だけ起動し、終了するまで stdout/stderr を中継する。
+/// 子プロセスが異常終了（非ゼロ終了コード）した場合に `true` を返す。
+async fn run_sidecar_once(app: &AppHandle, port: u16) -> bool {
+    let sidecar_command = match app.shell().sidecar("djaly-server") {
+        Ok(cmd) => cmd.env("DJALY_PORT", port.to_string()),
+        Err(err) => {
+            eprintln!("Failed to resolve sidecar command: {}", err);
+            return true;
+        }
+    };
+
+    println!("Attempting to spawn sidecar with port: {}", port);
+    let (mut rx, child) = match sidecar_command.spawn() {
+        Ok(pair) => pair,
+        Err(err) => {
+            eprintln!("Failed to spawn sidecar: {}", err);
+            return true;
+        }
+    };
+
+    // 終了時に kill できるよう子プロセスのハンドルを managed state に預ける。
+    *app.state::<ChildState>().0.lock().unwrap() = Some(child);
+
+    // バックエンドが実際に応答するまで待ってから healthy を通知する。
+    {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if wait_for_health(port, Duration::from_secs(30)).await {
+                let _ = app.emit("sidecar-status", SidecarStatus { state: "healthy".into(), port });
+            } else {
+                eprintln!("Health check timed out for sidecar on port {}", port);
+            }
+        });
+    }
+
+    let mut crashed = false;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line) => {
+                println!("[PY]: {}", String::from_utf8_lossy(&line));
+            }
+            CommandEvent::Stderr(line) => {
+                eprintln!("[PY ERR]: {}", String::from_utf8_lossy(&line));
+            }
+            CommandEvent::Terminated(payload) => {
+                if payload.code.unwrap_or(-1) != 0 {
+                    crashed = true;
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+    // 子プロセスはすでに終了しているのでハンドルを破棄する。
+    let _ = app.state::<ChildState>().0.lock().unwrap().take();
+    crashed
+}
+
+/// サイドカーを監督するタスクを起動する。
+/// 子プロセスが落ちるたびに空きポートを取り直して指数バックオフで再起動し、
+/// 状態遷移を `sidecar-status` イベントで webview に通知する。
+fn spawn_supervisor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        const MIN_BACKOFF: Duration = Duration::from_millis(500);
+        const MAX_BACKOFF: Duration = Duration::from_secs(30);
+        let mut backoff = MIN_BACKOFF;
+
+        loop {
+            // 終了処理中なら新たなサイドカーを起動しない（kill 後の復活を防ぐ）。
+            if app.state::<ShutdownFlag>().0.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let port = match find_free_port() {
+                Ok(port) => port,
+                Err(err) => {
+                    eprintln!("Failed to acquire a free port: {}", err);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    continue;
+                }
+            };
+
+            *app.state::<ServerState>().port.lock().unwrap() = port;
+            let _ = app.emit("sidecar-status", SidecarStatus { state: "spawning".into(), port });
+
+            let started = Instant::now();
+            let crashed = run_sidecar_once(&app, port).await;
+            let uptime = started.elapsed();
+
+            // 終了処理中の kill による終了は再起動対象にしない。
+            if app.state::<ShutdownFlag>().0.load(Ordering::SeqCst) {
+                break;
+            }
+
+            // 異常終了のときだけ "crashed" を通知する。正常終了は "stopped" として区別する。
+            let state = if crashed { "crashed" } else { "stopped" };
+            let _ = app.emit("sidecar-status", SidecarStatus { state: state.into(), port });
+            if crashed {
+                eprintln!("Sidecar crashed after {:?}; restarting", uptime);
+            } else {
+                eprintln!("Sidecar exited after {:?}; restarting", uptime);
+            }
+
+            // 十分に長く稼働できたならバックオフを初期値に戻す。
+            if uptime > Duration::from_secs(60) {
+                backoff = MIN_BACKOFF;
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    });
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         // 開発者ツールを有効化 (リリースビルドでもF12/右クリックで開けるようにする)
-        .plugin(tauri_plugin_devtools::init()) 
+        .plugin(tauri_plugin_devtools::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_http::init())
+        .manage(ServerState::default())
+        .manage(ChildState::default())
+        .manage(ShutdownFlag::default())
+        .manage(PendingUpdate::default())
+        .invoke_handler(tauri::generate_handler![
+            get_server_port,
+            set_locale,
+            check_for_update,
+            install_update
+        ])
         .menu(|handle| {
-            let menu = Menu::new(handle)?;
-            
-            #[cfg(target_os = "macos")]
-            {
-                let app_menu = Submenu::new(handle, "Djaly", true)?;
-                app_menu.append(&PredefinedMenuItem::hide(handle, None)?)?;
-                app_menu.append(&PredefinedMenuItem::hide_others(handle, None)?)?;
-                app_menu.append(&PredefinedMenuItem::quit(handle, None)?)?;
-                menu.append(&app_menu)?;
-            }
-            
-            let edit_menu = Submenu::new(handle, "Edit", true)?;
-            edit_menu.append(&PredefinedMenuItem::undo(handle, None)?)?;
-            edit_menu.append(&PredefinedMenuItem::redo(handle, None)?)?;
-            edit_menu.append(&PredefinedMenuItem::separator(handle)?)?;
-            edit_menu.append(&PredefinedMenuItem::cut(handle, None)?)?;
-            edit_menu.append(&PredefinedMenuItem::copy(handle, None)?)?;
-            edit_menu.append(&PredefinedMenuItem::paste(handle, None)?)?;
-            edit_menu.append(&PredefinedMenuItem::select_all(handle, None)?)?;
-            menu.append(&edit_menu)?;
-
-            let view_menu = Submenu::new(handle, "View", true)?;
-            view_menu.append(&PredefinedMenuItem::fullscreen(handle, None)?)?;
-            view_menu.append(&MenuItem::with_id(handle, "toggle_devtools", "Toggle Developer Tools", true, None::<&str>)?)?;
-            menu.append(&view_menu)?;
-            
-            Ok(menu)
+            // メニュー構築前にロケールを確定させる (永続化設定 → OS 言語 → en)。
+            rust_i18n::set_locale(&detect_locale(handle));
+            build_menu(handle)
         })
         .on_menu_event(|app, event| {
-            if event.id() == "toggle_devtools" {
-                if let Some(window) = app.get_webview_window("main") {
-                     if window.is_devtools_open() {
-                         window.close_devtools();
-                     } else {
-                         window.open_devtools();
-                     }
+            match event.id().as_ref() {
+                "toggle_devtools" => {
+                    if let Some(window) = app.get_webview_window("main") {
+                        if window.is_devtools_open() {
+                            window.close_devtools();
+                        } else {
+                            window.open_devtools();
+                        }
+                    }
+                }
+                "check_for_update" => {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        match perform_update_check(&app).await {
+                            Ok(Some(info)) => {
+                                let _ = app.emit("update-available", info);
+                            }
+                            Ok(None) => {
+                                let _ = app.emit("update-not-available", ());
+                            }
+                            Err(err) => eprintln!("Update check failed: {}", err),
+                        }
+                    });
                 }
+                "about" => {
+                    let version = app.package_info().version.to_string();
+                    app.dialog()
+                        .message(t!("menu.help.about_message", version => version))
+                        .title(t!("menu.help.about"))
+                        .show(|_| {});
+                }
+                _ => {}
             }
         })
         .setup(|app| {
+            // トレイとアップデート確認はサイドカーとは独立した機能なので、
+            // TAURI_SKIP_SIDECAR に左右されないよう早期 return より前で初期化する。
+
+            // ローカライズされたトレイアイコンを生成する。
+            build_tray(&app.handle().clone())?;
+
+            // 起動時に一度だけアップデートを自動確認する (TAURI_SKIP_UPDATE_CHECK で無効化可能)。
+            if env::var("TAURI_SKIP_UPDATE_CHECK").is_err() {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    // ウィンドウの初期化と競合しないよう少しデバウンスする。
+                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    match perform_update_check(&handle).await {
+                        Ok(Some(info)) => {
+                            let _ = handle.emit("update-available", info);
+                        }
+                        Ok(None) => {}
+                        Err(err) => eprintln!("Startup update check failed: {}", err),
+                    }
+                });
+            }
+
             // CI環境やビルド時はサイドカーを起動しない
             if env::var("CI").is_ok() || env::var("TAURI_SKIP_SIDECAR").is_ok() {
                 println!("Skipping sidecar startup (CI/build environment)");
                 return Ok(());
             }
 
-            // サイドカーの起動
-            // 本番環境（リリースビルド）では競合しにくいポートを使用する
-            // 開発環境ではデフォルトの8001を使用
-            #[cfg(debug_assertions)]
-            let port = "8001";
-            #[cfg(not(debug_assertions))]
-            let port = "48123"; // 競合しにくいポート番号
-
-            let sidecar_command = app.shell().sidecar("djaly-server")
-                .unwrap()
-                .env("DJALY_PORT", port);
-            
-            // コマンドの実行結果を詳細にログ出力
-            println!("Attempting to spawn sidecar with port: {}", port);
-
-            let (mut _rx, _child) = sidecar_command
-                .spawn()
-                .expect("Failed to spawn sidecar");
-
-            // 非同期でログを出力するスレッドを作成（デバッグ用）
-            tauri::async_runtime::spawn(async move {
-                while let Some(event) = _rx.recv().await {
-                    if let CommandEvent::Stdout(line) = event {
-                        println!("[PY]: {}", String::from_utf8_lossy(&line));
-                    } else if let CommandEvent::Stderr(line) = event {
-                        eprintln!("[PY ERR]: {}", String::from_utf8_lossy(&line));
-                    }
-                }
-            });
+            // サイドカーをスーパーバイザ配下で起動する。
+            // ポートは起動ごとに OS から空きを取得し、ServerState 経由でフロントに共有する。
+            spawn_supervisor(app.handle().clone());
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::ExitRequested { .. } | tauri::RunEvent::Exit = event {
+                // まずスーパーバイザに終了を知らせ、kill 後にサイドカーが再起動しないようにする。
+                app_handle.state::<ShutdownFlag>().0.store(true, Ordering::SeqCst);
+                // アプリ終了時 (ExitRequested / Exit) にサイドカーを停止し、ポートの取り残しを防ぐ。
+                // アプリのプロセス自体が強制終了・クラッシュした場合、このハンドラは実行されない。
+                // macOS の Quit メニューやトレイの Quit もこの経路を通る。
+                kill_sidecar(app_handle);
+            }
+        });
+}